@@ -28,9 +28,16 @@
 //! timescan converts a time format string like `%d/%b/%Y:%H:%M:%S%.f` into a regular expression
 //! that can efficiently locate timestamps in strings. It then converts those matched substrings
 //! into unix timestamps (integers) and returns them to you.
+//!
+//! Once you have a `Vec<i64>` of timestamps, [`build_sparkline`] renders them as a compact
+//! terminal histogram showing when activity happened.
 
+mod multi_format_finder;
+mod sparkline;
 mod timestamp_finder;
 
+pub use crate::multi_format_finder::MultiFormatFinder;
+pub use crate::sparkline::{build_sparkline, timestamp_frequency_distribution};
 pub use crate::timestamp_finder::TimestampFinder;
 
 #[cfg(test)]
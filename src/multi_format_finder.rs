@@ -0,0 +1,130 @@
+use crate::timestamp_finder::TimestampFinder;
+use anyhow::Result;
+use regex::Regex;
+use std::io::{prelude::*, BufReader};
+
+/// Finds timestamps in strings that may use any of several candidate formats, trying each
+/// format in order and reporting which one matched.
+///
+/// Real log streams often mix formats (CLF, ISO-8601, syslog, bare epoch), so unlike
+/// `TimestampFinder`, callers don't need to know the one exact format up front.
+pub struct MultiFormatFinder {
+    /// One `TimestampFinder` per candidate format, in the order they were given. Matching and
+    /// parsing is delegated to these rather than duplicated here, so offset handling (`%z`
+    /// honored, `%Z` matched but not honored, see `TimestampFinder`) stays identical everywhere
+    /// in the crate.
+    finders: Vec<TimestampFinder>,
+    /// Alternation of every candidate regex, used as a cheap pre-filter so lines that match
+    /// none of the formats don't pay for each authoritative parse attempt.
+    prefilter: Regex,
+}
+
+impl MultiFormatFinder {
+    /// Creates a new MultiFormatFinder from an ordered list of candidate formats. Each format
+    /// must use specifiers recognized by `TimestampFinder::new_with_format`. The per-format
+    /// finders (and the combined pre-filter) are compiled once here, at construction.
+    pub fn new(datetime_formats: Vec<String>) -> Result<Self> {
+        let finders = datetime_formats
+            .iter()
+            .map(|format| TimestampFinder::new_with_format(format))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let prefilter_pattern = finders
+            .iter()
+            .map(|finder| format!("(?:{})", finder.regex().as_str()))
+            .collect::<Vec<_>>()
+            .join("|");
+        let prefilter = Regex::new(&prefilter_pattern)?;
+
+        Ok(MultiFormatFinder { finders, prefilter })
+    }
+
+    /// Finds a timestamp in a string using the first candidate format that both matches and
+    /// parses successfully, returning its index into the format list along with the unix
+    /// timestamp.
+    pub fn find_timestamp(&self, s: &str) -> Option<(usize, i64)> {
+        if !self.prefilter.is_match(s) {
+            return None;
+        }
+
+        self.finders
+            .iter()
+            .enumerate()
+            .find_map(|(i, finder)| finder.find_timestamp(s).map(|timestamp| (i, timestamp)))
+    }
+
+    /// Scans a reader for times matching any of the configured formats, returning them as a
+    /// vector of unix timestamps.
+    pub fn scan<R>(&self, reader: R) -> Result<Vec<i64>>
+    where
+        R: Read,
+    {
+        let timestamps = BufReader::new(reader)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| self.find_timestamp(&line).map(|(_, timestamp)| timestamp))
+            .collect();
+        Ok(timestamps)
+    }
+
+    /// Scans a reader like `scan`, but also reports which format index matched each timestamp.
+    pub fn scan_with_formats<R>(&self, reader: R) -> Result<Vec<(usize, i64)>>
+    where
+        R: Read,
+    {
+        let timestamps = BufReader::new(reader)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| self.find_timestamp(&line))
+            .collect();
+        Ok(timestamps)
+    }
+}
+
+#[test]
+fn multi_format_finder_picks_first_matching_format() {
+    let finder = MultiFormatFinder::new(vec![
+        "%Y-%m-%dT%H:%M:%S".to_string(),
+        "%d/%b/%Y:%H:%M:%S%.f".to_string(),
+        "%s".to_string(),
+    ])
+    .unwrap();
+
+    let (format_index, timestamp) = finder
+        .find_timestamp("06/Jan/2006:13:04:05.000 some log line")
+        .unwrap();
+    assert_eq!(format_index, 1);
+    assert_eq!(timestamp, 1136552645);
+}
+
+#[test]
+fn multi_format_finder_honors_offset_specifier() {
+    let finder = MultiFormatFinder::new(vec!["%Y-%m-%dT%H:%M:%S%z".to_string()]).unwrap();
+
+    // 13:04:05+09:00 is 04:04:05 UTC, not 13:04:05 UTC.
+    let (format_index, timestamp) = finder
+        .find_timestamp("2006-01-06T13:04:05+09:00 some log")
+        .unwrap();
+    assert_eq!(format_index, 0);
+    assert_eq!(timestamp, 1136520245);
+}
+
+#[test]
+fn multi_format_finder_scan_with_formats_reports_which_format_matched() {
+    let finder =
+        MultiFormatFinder::new(vec!["%Y-%m-%dT%H:%M:%S".to_string(), "%s".to_string()]).unwrap();
+
+    let log = "2006-01-06T13:04:05 line one\n1136552645 line two\n";
+    let results = finder.scan_with_formats(log.as_bytes()).unwrap();
+    assert_eq!(results, vec![(0, 1136552645), (1, 1136552645)]);
+}
+
+#[test]
+fn multi_format_finder_scan_returns_bare_timestamps() {
+    let finder =
+        MultiFormatFinder::new(vec!["%Y-%m-%dT%H:%M:%S".to_string(), "%s".to_string()]).unwrap();
+
+    let log = "2006-01-06T13:04:05 line one\n1136552645 line two\n";
+    let timestamps = finder.scan(log.as_bytes()).unwrap();
+    assert_eq!(timestamps, vec![1136552645, 1136552645]);
+}
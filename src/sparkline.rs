@@ -0,0 +1,100 @@
+//! Turns a set of unix timestamps into a terminal sparkline, so callers can see at a glance
+//! when activity happened without plotting the full timestamp set.
+
+/// The Unicode block glyphs used to render a sparkline, from emptiest to fullest.
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Buckets `timestamps` into `buckets` time windows spanning `min..=max` and returns the count
+/// of timestamps falling into each bucket.
+///
+/// The first `buckets - 1` windows each cover an equal share of `max - min`; the last bucket
+/// only receives timestamps equal to `max`.
+///
+/// If all timestamps are identical (or there's only one), every timestamp falls into bucket 0.
+pub fn timestamp_frequency_distribution(timestamps: &[i64], buckets: usize) -> Vec<usize> {
+    let mut distribution = vec![0; buckets];
+    if timestamps.is_empty() || buckets == 0 {
+        return distribution;
+    }
+
+    let min = *timestamps.iter().min().unwrap();
+    let max = *timestamps.iter().max().unwrap();
+    let span = max - min;
+
+    for &timestamp in timestamps {
+        let bucket = if span == 0 {
+            0
+        } else {
+            (((timestamp - min) as f64 / span as f64) * (buckets - 1) as f64).floor() as usize
+        };
+        distribution[bucket] += 1;
+    }
+
+    distribution
+}
+
+/// Renders `timestamps` as a terminal sparkline, with `length` buckets spanning the full
+/// range of the data. Each bucket is scaled to one of eight Unicode block glyphs based on
+/// how many timestamps fall into it relative to the least and most populated buckets.
+///
+/// Returns an empty string for empty input.
+pub fn build_sparkline(timestamps: &[i64], length: usize) -> String {
+    if timestamps.is_empty() || length == 0 {
+        return String::new();
+    }
+
+    let distribution = timestamp_frequency_distribution(timestamps, length);
+    let min_count = *distribution.iter().min().unwrap();
+    let max_count = *distribution.iter().max().unwrap();
+    let count_span = max_count - min_count;
+
+    distribution
+        .iter()
+        .map(|&count| {
+            let glyph_index = if count_span == 0 {
+                BLOCKS.len() - 1
+            } else {
+                (((count - min_count) as f64 / count_span as f64) * (BLOCKS.len() - 1) as f64)
+                    .round() as usize
+            };
+            BLOCKS[glyph_index]
+        })
+        .collect()
+}
+
+#[test]
+fn frequency_distribution_counts_every_timestamp_once() {
+    let timestamps = vec![0, 10, 20, 30, 40, 50, 60, 70, 80, 90, 100];
+    let distribution = timestamp_frequency_distribution(&timestamps, 5);
+    assert_eq!(distribution.len(), 5);
+    assert_eq!(distribution.iter().sum::<usize>(), timestamps.len());
+}
+
+#[test]
+fn frequency_distribution_handles_single_timestamp() {
+    let distribution = timestamp_frequency_distribution(&[42], 5);
+    assert_eq!(distribution, vec![1, 0, 0, 0, 0]);
+}
+
+#[test]
+fn frequency_distribution_handles_empty_input() {
+    let distribution = timestamp_frequency_distribution(&[], 5);
+    assert_eq!(distribution, vec![0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn sparkline_handles_empty_input() {
+    assert_eq!(build_sparkline(&[], 10), "");
+}
+
+#[test]
+fn sparkline_renders_single_timestamp_as_one_full_bar() {
+    assert_eq!(build_sparkline(&[42], 1), "█");
+}
+
+#[test]
+fn sparkline_has_one_glyph_per_bucket() {
+    let timestamps = vec![0, 5, 10, 10, 10, 20, 30, 30, 30, 30, 40];
+    let sparkline = build_sparkline(&timestamps, 4);
+    assert_eq!(sparkline.chars().count(), 4);
+}
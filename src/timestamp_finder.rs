@@ -1,12 +1,27 @@
 use anyhow::Result;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
 use regex::Regex;
 use std::io::{prelude::*, BufReader};
+use std::ops::Range;
+
+/// The granularity at which a matched timestamp is converted to a unix epoch value.
+enum Precision {
+    Seconds,
+    Millis,
+    Nanos,
+}
 
 /// Finds timestamps in strings based on a configurable format
 pub struct TimestampFinder {
     datetime_format: String,
     regex: Regex,
+    /// Source timezone to assume when `datetime_format` has no offset specifier of its own.
+    tz: Option<FixedOffset>,
+    /// Whether `datetime_format` itself carries a numeric UTC offset (`%z`), in which case the
+    /// matched text is parsed as a `DateTime` rather than assumed to be in `tz`. `%Z` (a bare
+    /// zone name like "PST") doesn't count: chrono parses and discards it without producing an
+    /// offset, so formats using only `%Z` stay on the naive/`tz`-assumed path below.
+    has_offset_specifier: bool,
 }
 
 impl TimestampFinder {
@@ -34,27 +49,124 @@ impl TimestampFinder {
     /// | %B        | Full month name. Also accepts corresponding abbreviation in parsing. |
     /// | %h        | Same as %b. |
     /// | %d        | Day number (01--31), zero-padded to 2 digits. |
+    /// | %e        | Day number (1--31), space-padded to 2 digits. |
+    /// | %a        | Abbreviated weekday name. |
+    /// | %A        | Full weekday name. Also accepts corresponding abbreviation in parsing. |
+    /// | %j        | Day of the year (001--366), zero-padded to 3 digits. |
     /// | %H        | Hour number (00--23), zero-padded to 2 digits. |
+    /// | %I        | Hour number in 12-hour clocks (01--12), zero-padded to 2 digits. |
+    /// | %p        | `AM` or `PM` in 12-hour clocks. |
+    /// | %P        | `am` or `pm` in 12-hour clocks. |
     /// | %M        | Minute number (00--59), zero-padded to 2 digits. |
     /// | %S        | Second number (00--60), zero-padded to 2 digits. |
     /// | %.f       | Similar to .%f but left-aligned. These all consume the leading dot. |
+    /// | %3f       | Fixed-width millisecond fraction, without the leading dot. |
+    /// | %6f       | Fixed-width microsecond fraction, without the leading dot. |
+    /// | %9f       | Fixed-width nanosecond fraction, without the leading dot. |
+    /// | %z        | Offset from UTC, e.g. `+09:00` or `-0400`. Honored when converting to a timestamp. |
+    /// | %Z        | Time zone name, e.g. `UTC` or `PST`. Matched but NOT honored: chrono can't turn a bare zone name into an offset, so the match is interpreted via `tz` (see `new_with_format_and_tz`), defaulting to UTC. |
     /// | %s        | UNIX timestamp. Seconds since 1970-01-01 00:00 UTC. |
+    /// | %T        | Shorthand for `%H:%M:%S`. |
+    /// | %R        | Shorthand for `%H:%M`. |
+    /// | %F        | Shorthand for `%Y-%m-%d`. |
+    /// | %D        | Shorthand for `%m/%d/%y`. |
     pub fn new_with_format(datetime_format: &str) -> Result<Self, anyhow::Error> {
+        Self::new_with_format_and_tz(datetime_format, None)
+    }
+
+    /// Creates a new TimestampFinder, given a format and the timezone that timestamps
+    /// matching it should be assumed to be in.
+    ///
+    /// If `datetime_format` itself contains a numeric offset specifier (`%z`), the offset
+    /// parsed out of each matched timestamp takes precedence and `tz` is ignored. Otherwise,
+    /// every matched timestamp is treated as local time in `tz` before being converted to UTC.
+    /// Pass `None` to keep assuming UTC, as `new_with_format` does. Note that `%Z` (a bare zone
+    /// name like "PST") does NOT supply a usable offset, so formats using only `%Z` also fall
+    /// into this `tz`-assumed path; the zone name is matched but otherwise ignored.
+    pub fn new_with_format_and_tz(
+        datetime_format: &str,
+        tz: Option<FixedOffset>,
+    ) -> Result<Self, anyhow::Error> {
         let datetime_regex = Self::strftime_to_regex(datetime_format);
         let regex = Regex::new(&datetime_regex)?;
+        let has_offset_specifier = datetime_format.contains("%z");
 
         Ok(TimestampFinder {
             datetime_format: datetime_format.to_string(),
             regex,
+            tz,
+            has_offset_specifier,
         })
     }
 
     /// Finds a timestamp in a string, returning it as a unix timestamp
+    ///
+    /// If the configured format carries its own numeric offset (`%z`), that offset is honored.
+    /// Otherwise the match is interpreted in the timezone passed to `new_with_format_and_tz`,
+    /// defaulting to UTC. Either way, the returned timestamp is seconds since the UTC epoch.
     pub fn find_timestamp(&self, s: &str) -> Option<i64> {
         let regex_match = self.regex.captures(s)?.get(0)?;
-        let datetime =
-            NaiveDateTime::parse_from_str(regex_match.as_str(), &self.datetime_format).ok()?;
-        Some(datetime.timestamp())
+        self.parse_timestamp(regex_match.as_str(), Precision::Seconds)
+    }
+
+    /// Like `find_timestamp`, but preserves millisecond precision instead of truncating to
+    /// whole seconds. Use with a format that carries sub-second specifiers, e.g. `%.f` or `%3f`.
+    pub fn find_timestamp_millis(&self, s: &str) -> Option<i64> {
+        let regex_match = self.regex.captures(s)?.get(0)?;
+        self.parse_timestamp(regex_match.as_str(), Precision::Millis)
+    }
+
+    /// Like `find_timestamp`, but preserves nanosecond precision instead of truncating to
+    /// whole seconds. Use with a format that carries sub-second specifiers, e.g. `%.f` or `%9f`.
+    pub fn find_timestamp_nanos(&self, s: &str) -> Option<i64> {
+        let regex_match = self.regex.captures(s)?.get(0)?;
+        self.parse_timestamp(regex_match.as_str(), Precision::Nanos)
+    }
+
+    /// Exposes the compiled regex, for callers in this crate (namely `MultiFormatFinder`) that
+    /// need to combine it with other finders' regexes rather than duplicating
+    /// `strftime_to_regex` + parsing logic of their own.
+    pub(crate) fn regex(&self) -> &Regex {
+        &self.regex
+    }
+
+    /// Parses a single matched substring into a unix timestamp at the given precision, honoring
+    /// `has_offset_specifier` and `tz` the same way `find_timestamp` does.
+    fn parse_timestamp(&self, matched: &str, precision: Precision) -> Option<i64> {
+        let datetime = if self.has_offset_specifier {
+            DateTime::parse_from_str(matched, &self.datetime_format)
+                .ok()?
+                .with_timezone(&Utc)
+        } else {
+            let naive = NaiveDateTime::parse_from_str(matched, &self.datetime_format).ok()?;
+            match self.tz {
+                Some(tz) => tz.from_local_datetime(&naive).single()?.with_timezone(&Utc),
+                None => naive.and_utc(),
+            }
+        };
+
+        // `timestamp_nanos_opt` can return `None` for dates outside ~1677-2262, which the
+        // regexes built from `%Y` etc. happily match, so this must stay fallible rather than
+        // calling the panicking `timestamp_nanos()` accessor.
+        match precision {
+            Precision::Seconds => Some(datetime.timestamp()),
+            Precision::Millis => Some(datetime.timestamp_millis()),
+            Precision::Nanos => datetime.timestamp_nanos_opt(),
+        }
+    }
+
+    /// Finds every timestamp on a line, returning each one's byte range within `s` alongside
+    /// its unix timestamp. Matches that fail to parse (which shouldn't normally happen, since
+    /// the regex was derived from the same format) are skipped rather than returned as errors.
+    pub fn find_all_timestamps(&self, s: &str) -> Vec<(Range<usize>, i64)> {
+        self.regex
+            .captures_iter(s)
+            .filter_map(|captures| {
+                let regex_match = captures.get(0)?;
+                let timestamp = self.parse_timestamp(regex_match.as_str(), Precision::Seconds)?;
+                Some((regex_match.range(), timestamp))
+            })
+            .collect()
     }
 
     /// Scans a reader for times matching the given format, returning them as a vector of unix timestamps
@@ -70,20 +182,82 @@ impl TimestampFinder {
         Ok(timestamps)
     }
 
-    fn strftime_to_regex(time_format: &str) -> String {
-        time_format
+    /// Scans a reader like `scan`, but returns every timestamp found on every line along with
+    /// its byte range in the overall stream, so callers can map a timestamp back to its
+    /// location for highlighting or extraction.
+    pub fn scan_with_positions<R>(&self, reader: R) -> Result<Vec<(Range<usize>, i64)>>
+    where
+        R: Read,
+    {
+        let mut offset = 0;
+        let mut results = Vec::new();
+        let mut reader = BufReader::new(reader);
+        let mut raw_line = Vec::new();
+
+        loop {
+            raw_line.clear();
+            let bytes_read = reader.read_until(b'\n', &mut raw_line)?;
+            if bytes_read == 0 {
+                break;
+            }
+
+            // Strip the line terminator ourselves (rather than via `lines()`, which silently
+            // normalizes both `\n` and `\r\n`) so `offset` always advances by the exact number
+            // of bytes consumed, regardless of which separator the stream actually used.
+            let mut line_bytes = raw_line.as_slice();
+            if line_bytes.last() == Some(&b'\n') {
+                line_bytes = &line_bytes[..line_bytes.len() - 1];
+                if line_bytes.last() == Some(&b'\r') {
+                    line_bytes = &line_bytes[..line_bytes.len() - 1];
+                }
+            }
+
+            if let Ok(line) = std::str::from_utf8(line_bytes) {
+                for (range, timestamp) in self.find_all_timestamps(line) {
+                    results.push((offset + range.start..offset + range.end, timestamp));
+                }
+            }
+
+            offset += bytes_read;
+        }
+
+        Ok(results)
+    }
+
+    pub(crate) fn strftime_to_regex(time_format: &str) -> String {
+        // Composite specifiers expand to their component specifiers first, so that
+        // e.g. %T becomes %H:%M:%S before the single-specifier substitutions below run.
+        let expanded = time_format
+            .replace("%T", "%H:%M:%S")
+            .replace("%R", "%H:%M")
+            .replace("%F", "%Y-%m-%d")
+            .replace("%D", "%m/%d/%y");
+
+        expanded
             .replace("%Y", r"\d{1,4}")
             .replace("%C", r"\d{1,2}")
-            .replace("%y", r"\d{1,2")
+            .replace("%y", r"\d{1,2}")
             .replace("%m", r"\d{1,2}")
             .replace("%b", r"[A-Za-z]{3}")
-            .replace("%B", r"[A-Za-z]{3,4,5,6,7,8,9}")
+            .replace("%B", r"[A-Za-z]{3,9}")
             .replace("%h", r"[A-Za-z]{3}")
             .replace("%d", r"\d{1,2}")
+            .replace("%e", r"\s?\d{1,2}")
+            .replace("%A", r"[A-Za-z]{3,9}")
+            .replace("%a", r"[A-Za-z]{3,9}")
+            .replace("%j", r"\d{1,3}")
             .replace("%H", r"\d{1,2}")
+            .replace("%I", r"\d{1,2}")
+            .replace("%p", r"[AaPp][Mm]")
+            .replace("%P", r"[AaPp][Mm]")
             .replace("%M", r"\d{1,2}")
             .replace("%S", r"\d{1,2}")
-            .replace("%.f", r"\d{1,}")
+            .replace("%.f", r"(?:\.\d+)?")
+            .replace("%3f", r"\d{3}")
+            .replace("%6f", r"\d{6}")
+            .replace("%9f", r"\d{9}")
+            .replace("%z", r"[+-]\d{2}:?\d{2}")
+            .replace("%Z", r"[A-Za-z]{2,5}")
             .replace("%s", r"\d{1,10}")
         // TODO: Add support for remaining characters. https://docs.rs/chrono/0.4.13/chrono/format/strftime/index.html
     }
@@ -98,6 +272,133 @@ fn timestamp_finder_strftime_to_regex() {
     };
 
     convert_compile_match("%d/%b/%Y:%H:%M:%S%.f", "06/Jan/2006:13:04:05.000");
+    convert_compile_match("%e %b %Y", "6 Jan 2006");
+    convert_compile_match("%a %b %e", "Fri Jan 6");
+    convert_compile_match("%A, %B %d", "Friday, January 06");
+    convert_compile_match("%Y-%j", "2006-006");
+    convert_compile_match("%I:%M %p", "01:04 PM");
+    convert_compile_match("%I:%M %P", "01:04 pm");
+    convert_compile_match("%Y-%m-%dT%H:%M:%S%z", "2006-01-06T13:04:05+09:00");
+    convert_compile_match("%H:%M:%S %Z", "13:04:05 PST");
+    convert_compile_match("%T", "13:04:05");
+    convert_compile_match("%R", "13:04");
+    convert_compile_match("%F", "2006-01-06");
+    convert_compile_match("%D", "01/06/06");
+    convert_compile_match("%H:%M:%S.%3f", "13:04:05.123");
+    convert_compile_match("%H:%M:%S.%6f", "13:04:05.123456");
+    convert_compile_match("%H:%M:%S.%9f", "13:04:05.123456789");
+}
+
+#[test]
+fn timestamp_finder_offset_specifier() {
+    let format = "%d/%b/%Y:%H:%M:%S%z";
+    let date_finder = TimestampFinder::new_with_format(format).unwrap();
+
+    // 13:04:05+09:00 is 04:04:05 UTC
+    let log = "06/Jan/2006:13:04:05+09:00 ip-10-1-26-81 haproxy[20128]: 54.242.135...";
+    let timestamp = date_finder.find_timestamp(log).unwrap();
+    assert_eq!(timestamp, 1136520245);
+}
+
+#[test]
+fn timestamp_finder_zone_name_specifier_is_matched_but_not_honored() {
+    let format = "%d/%b/%Y:%H:%M:%S %Z";
+    let date_finder = TimestampFinder::new_with_format(format).unwrap();
+
+    // "%Z" matches the zone name but can't supply an offset from it, so the match is
+    // interpreted as UTC rather than failing to parse.
+    let log = "06/Jan/2006:13:04:05 PST some log line";
+    let timestamp = date_finder.find_timestamp(log).unwrap();
+    assert_eq!(timestamp, 1136552645);
+}
+
+#[test]
+fn timestamp_finder_assumed_tz() {
+    let format = "%d/%b/%Y:%H:%M:%S";
+    let tz = FixedOffset::east_opt(9 * 3600).unwrap();
+    let date_finder = TimestampFinder::new_with_format_and_tz(format, Some(tz)).unwrap();
+
+    // 13:04:05 assumed to be JST (UTC+9) is 04:04:05 UTC
+    let log = "06/Jan/2006:13:04:05 ip-10-1-26-81 haproxy[20128]: 54.242.135...";
+    let timestamp = date_finder.find_timestamp(log).unwrap();
+    assert_eq!(timestamp, 1136520245);
+}
+
+#[test]
+fn find_all_timestamps_returns_every_match_with_its_range() {
+    let date_finder = TimestampFinder::new().unwrap();
+    let log = "request started [23/Nov/2019:06:26:40.781] and finished [23/Nov/2019:06:26:41.002]";
+
+    let matches = date_finder.find_all_timestamps(log);
+    assert_eq!(matches.len(), 2);
+
+    let (first_range, first_timestamp) = &matches[0];
+    assert_eq!(&log[first_range.clone()], "23/Nov/2019:06:26:40.781");
+    assert_eq!(*first_timestamp, 1574490400);
+
+    let (second_range, second_timestamp) = &matches[1];
+    assert_eq!(&log[second_range.clone()], "23/Nov/2019:06:26:41.002");
+    assert_eq!(*second_timestamp, 1574490401);
+}
+
+#[test]
+fn scan_with_positions_offsets_ranges_by_line() {
+    let date_finder = TimestampFinder::new().unwrap();
+    let log = "first [23/Nov/2019:06:26:40.781] line\nsecond [23/Nov/2019:06:26:41.002] line\n";
+
+    let matches = date_finder.scan_with_positions(log.as_bytes()).unwrap();
+    assert_eq!(matches.len(), 2);
+
+    let (first_range, first_timestamp) = &matches[0];
+    assert_eq!(&log[first_range.clone()], "23/Nov/2019:06:26:40.781");
+    assert_eq!(*first_timestamp, 1574490400);
+
+    let (second_range, second_timestamp) = &matches[1];
+    assert_eq!(&log[second_range.clone()], "23/Nov/2019:06:26:41.002");
+    assert_eq!(*second_timestamp, 1574490401);
+}
+
+#[test]
+fn scan_with_positions_handles_crlf_line_endings() {
+    let date_finder = TimestampFinder::new().unwrap();
+    let log = "first [23/Nov/2019:06:26:40.781] line\r\nsecond [23/Nov/2019:06:26:41.002] line\r\n";
+
+    let matches = date_finder.scan_with_positions(log.as_bytes()).unwrap();
+    assert_eq!(matches.len(), 2);
+
+    let (first_range, first_timestamp) = &matches[0];
+    assert_eq!(&log[first_range.clone()], "23/Nov/2019:06:26:40.781");
+    assert_eq!(*first_timestamp, 1574490400);
+
+    let (second_range, second_timestamp) = &matches[1];
+    assert_eq!(&log[second_range.clone()], "23/Nov/2019:06:26:41.002");
+    assert_eq!(*second_timestamp, 1574490401);
+}
+
+#[test]
+fn find_timestamp_millis_preserves_sub_second_precision() {
+    let date_finder = TimestampFinder::new().unwrap();
+    let log = "[23/Nov/2019:06:26:40.781] GET /";
+    let timestamp = date_finder.find_timestamp_millis(log).unwrap();
+    assert_eq!(timestamp, 1574490400781);
+}
+
+#[test]
+fn find_timestamp_nanos_preserves_sub_second_precision() {
+    let date_finder = TimestampFinder::new().unwrap();
+    let log = "[23/Nov/2019:06:26:40.781] GET /";
+    let timestamp = date_finder.find_timestamp_nanos(log).unwrap();
+    assert_eq!(timestamp, 1574490400781000000);
+}
+
+#[test]
+fn find_timestamp_nanos_returns_none_instead_of_panicking_out_of_range() {
+    let date_finder = TimestampFinder::new_with_format("%Y-%m-%d %H:%M:%S").unwrap();
+    let log = "event at 2300-01-01 00:00:00 happened";
+    assert_eq!(date_finder.find_timestamp_nanos(log), None);
+    // Seconds and millis precision stay in range for the same date.
+    assert!(date_finder.find_timestamp(log).is_some());
+    assert!(date_finder.find_timestamp_millis(log).is_some());
 }
 
 #[test]